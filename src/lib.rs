@@ -4,30 +4,91 @@
 //!
 //! - Merges multiple `/` into one.
 //! - Resolves and eliminates `..` and `.` if any.
-//! - Appends a trailing `/` if one is not present, and there is no file extension.
+//! - Applies the configured [`TrailingSlash`] policy.
 //!
 //! It will respond with a permanent redirect if the path was cleaned.
+//! That is the default: [`CleanPath::use_redirects`] picks another 3xx status,
+//! or rewrites the request URI in place instead of redirecting.
 //!
 //! ```rust
 //! use actix_web::{web, App, HttpResponse};
 //!
 //! # fn main() {
 //! let app = App::new()
-//!     .wrap(actix_clean_path::CleanPath)
+//!     .wrap(actix_clean_path::CleanPath::default())
 //!     .route("/", web::get().to(|| HttpResponse::Ok()));
 //! # }
 //! ```
 
 use actix_web::dev::{Service, ServiceRequest, ServiceResponse, Transform};
-use actix_web::http::{self, PathAndQuery, Uri};
+use actix_web::http::{self, PathAndQuery, StatusCode, Uri};
 use actix_web::{Error, HttpResponse};
 use futures_util::future::{ok, Either, LocalBoxFuture, Ready};
 use std::task::{Context, Poll};
 
+/// How a trailing `/` is handled once the path has been merged and resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrailingSlash {
+    /// Strip any trailing `/` (except for the root path).
+    Trim,
+    /// Only collapse repeated slashes and resolve `.`/`..`, leaving the
+    /// trailing slash exactly as it was sent.
+    MergeOnly,
+    /// Always append a trailing `/`.
+    Always,
+    /// Append a trailing `/` unless the final segment looks like it has a
+    /// file extension. This is the original behaviour of the middleware and
+    /// the [`Default`].
+    Extension,
+}
+
 /// `Middleware` to clean request's URI, and redirect if necessary.
 /// See module documenation for more.
-#[derive(Default, Clone, Copy)]
-pub struct CleanPath;
+#[derive(Debug, Clone, Copy)]
+pub struct CleanPath {
+    trailing_slash: TrailingSlash,
+    use_redirects: Option<StatusCode>,
+}
+
+impl Default for CleanPath {
+    fn default() -> Self {
+        CleanPath {
+            trailing_slash: TrailingSlash::Extension,
+            use_redirects: Some(StatusCode::PERMANENT_REDIRECT),
+        }
+    }
+}
+
+impl CleanPath {
+    /// Build a `CleanPath` with the given trailing-slash policy.
+    pub fn new(trailing_slash: TrailingSlash) -> Self {
+        CleanPath {
+            trailing_slash,
+            ..CleanPath::default()
+        }
+    }
+
+    /// Choose how a cleaned path is surfaced.
+    ///
+    /// `Some(status)` emits a redirect with the given status, while `None`
+    /// rewrites the request URI in place and passes it down the service chain
+    /// without an extra round trip.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `status` is not a 3xx redirection code.
+    pub fn use_redirects(mut self, use_redirects: Option<StatusCode>) -> Self {
+        if let Some(status) = use_redirects {
+            assert!(
+                status.is_redirection(),
+                "use_redirects expects a 3xx status, got {}",
+                status,
+            );
+        }
+        self.use_redirects = use_redirects;
+        self
+    }
+}
 
 impl<S, B> Transform<S> for CleanPath
 where
@@ -42,13 +103,19 @@ where
     type Future = Ready<Result<Self::Transform, Self::InitError>>;
 
     fn new_transform(&self, service: S) -> Self::Future {
-        ok(CleanPathNormalization { service })
+        ok(CleanPathNormalization {
+            service,
+            trailing_slash: self.trailing_slash,
+            use_redirects: self.use_redirects,
+        })
     }
 }
 
 #[doc(hidden)]
 pub struct CleanPathNormalization<S> {
     service: S,
+    trailing_slash: TrailingSlash,
+    use_redirects: Option<StatusCode>,
 }
 
 impl<S, B> Service for CleanPathNormalization<S>
@@ -75,17 +142,12 @@ where
         // non-allocating fast path
         if !original_path.contains("/.")
             && !original_path.contains("//")
-            && (has_ext(original_path) ^ trailing_slash)
+            && is_clean(self.trailing_slash, original_path, trailing_slash)
         {
             return Either::Right(Box::pin(self.service.call(req)));
         }
 
-        let mut path = path_clean::clean(&original_path);
-        if path != "/" {
-            if trailing_slash || !has_ext(&path) {
-                path.push('/');
-            }
-        }
+        let path = clean_path(self.trailing_slash, original_path, trailing_slash);
 
         if path != original_path {
             let mut parts = req.uri().clone().into_parts();
@@ -98,17 +160,66 @@ where
             parts.path_and_query = Some(PathAndQuery::from_maybe_shared(path).unwrap());
             let uri = Uri::from_parts(parts).unwrap();
 
-            Either::Left(ok(req.error_response(actix_web::Error::from(
-                HttpResponse::PermanentRedirect()
-                    .header(http::header::LOCATION, uri.to_string())
-                    .finish(),
-            ))))
+            match self.use_redirects {
+                Some(status) => Either::Left(ok(req.error_response(actix_web::Error::from(
+                    HttpResponse::build(status)
+                        .header(http::header::LOCATION, uri.to_string())
+                        .finish(),
+                )))),
+                None => {
+                    let mut req = req;
+                    req.head_mut().uri = uri;
+                    Either::Right(Box::pin(self.service.call(req)))
+                }
+            }
         } else {
             Either::Right(Box::pin(self.service.call(req)))
         }
     }
 }
 
+/// Whether a `//`- and `/.`-free path already satisfies the policy, letting
+/// us skip the allocating clean entirely.
+fn is_clean(policy: TrailingSlash, path: &str, trailing_slash: bool) -> bool {
+    match policy {
+        TrailingSlash::Trim => path == "/" || !trailing_slash,
+        TrailingSlash::MergeOnly => true,
+        TrailingSlash::Always => trailing_slash,
+        TrailingSlash::Extension => has_ext(path) ^ trailing_slash,
+    }
+}
+
+/// Merge, resolve and then apply the trailing-slash policy to `path`.
+fn clean_path(policy: TrailingSlash, path: &str, trailing_slash: bool) -> String {
+    let mut path = path_clean::clean(path);
+    if path == "/" {
+        return path;
+    }
+    match policy {
+        TrailingSlash::Trim => {
+            while path.len() > 1 && path.ends_with('/') {
+                path.pop();
+            }
+        }
+        TrailingSlash::MergeOnly => {
+            if trailing_slash && !path.ends_with('/') {
+                path.push('/');
+            }
+        }
+        TrailingSlash::Always => {
+            if !path.ends_with('/') {
+                path.push('/');
+            }
+        }
+        TrailingSlash::Extension => {
+            if trailing_slash || !has_ext(&path) {
+                path.push('/');
+            }
+        }
+    }
+    path
+}
+
 fn has_ext(path: &str) -> bool {
     path.rfind('.')
         .map(|index| {
@@ -120,7 +231,7 @@ fn has_ext(path: &str) -> bool {
 
 #[cfg(test)]
 mod tests {
-    use super::CleanPath;
+    use super::{CleanPath, TrailingSlash};
     use actix_web::test::{call_service, init_service, TestRequest};
     use actix_web::{http, web, App, HttpResponse};
 
@@ -128,7 +239,7 @@ mod tests {
     async fn test_clean() {
         let mut app = init_service(
             App::new()
-                .wrap(CleanPath)
+                .wrap(CleanPath::default())
                 .service(web::resource("/*").to(|| HttpResponse::Ok())),
         )
         .await;
@@ -175,7 +286,7 @@ mod tests {
     async fn test_pristine() {
         let mut app = init_service(
             App::new()
-                .wrap(CleanPath)
+                .wrap(CleanPath::default())
                 .service(web::resource("/*").to(|| HttpResponse::Ok())),
         )
         .await;
@@ -187,4 +298,129 @@ mod tests {
             assert!(res.status().is_success(), "for {}", given);
         }
     }
+
+    #[actix_rt::test]
+    async fn test_trim() {
+        let mut app = init_service(
+            App::new()
+                .wrap(CleanPath::new(TrailingSlash::Trim))
+                .service(web::resource("/*").to(|| HttpResponse::Ok())),
+        )
+        .await;
+
+        let cases = vec![
+            ("/a/", "/a"),
+            ("//a//b//", "/a/b"),
+            ("/a//b", "/a/b"),
+            ("/m.js/", "/m.js"),
+        ];
+        for (given, clean) in cases.iter() {
+            let req = TestRequest::with_uri(given).to_request();
+            let res = call_service(&mut app, req).await;
+            assert!(res.status().is_redirection(), "for {}", given);
+            assert_eq!(
+                &res.headers()
+                    .get(http::header::LOCATION)
+                    .unwrap()
+                    .to_str()
+                    .unwrap(),
+                clean,
+                "for {}",
+                given,
+            );
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_always() {
+        let mut app = init_service(
+            App::new()
+                .wrap(CleanPath::new(TrailingSlash::Always))
+                .service(web::resource("/*").to(|| HttpResponse::Ok())),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/m.js").to_request();
+        let res = call_service(&mut app, req).await;
+        assert!(res.status().is_redirection());
+        assert_eq!(
+            res.headers()
+                .get(http::header::LOCATION)
+                .unwrap()
+                .to_str()
+                .unwrap(),
+            "/m.js/",
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_merge_only() {
+        let mut app = init_service(
+            App::new()
+                .wrap(CleanPath::new(TrailingSlash::MergeOnly))
+                .service(web::resource("/*").to(|| HttpResponse::Ok())),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("/a//b").to_request();
+        let res = call_service(&mut app, req).await;
+        assert!(res.status().is_redirection());
+        assert_eq!(
+            res.headers()
+                .get(http::header::LOCATION)
+                .unwrap()
+                .to_str()
+                .unwrap(),
+            "/a/b",
+        );
+
+        let req = TestRequest::with_uri("/a/b").to_request();
+        let res = call_service(&mut app, req).await;
+        assert!(res.status().is_success());
+    }
+
+    #[actix_rt::test]
+    async fn test_redirect_status() {
+        let mut app = init_service(
+            App::new()
+                .wrap(CleanPath::default().use_redirects(Some(http::StatusCode::MOVED_PERMANENTLY)))
+                .service(web::resource("/*").to(|| HttpResponse::Ok())),
+        )
+        .await;
+
+        let req = TestRequest::with_uri("//a//b").to_request();
+        let res = call_service(&mut app, req).await;
+        assert_eq!(res.status(), http::StatusCode::MOVED_PERMANENTLY);
+        assert_eq!(
+            res.headers()
+                .get(http::header::LOCATION)
+                .unwrap()
+                .to_str()
+                .unwrap(),
+            "/a/b/",
+        );
+    }
+
+    #[actix_rt::test]
+    #[should_panic]
+    async fn test_redirect_status_rejects_non_3xx() {
+        let _ = CleanPath::default().use_redirects(Some(http::StatusCode::OK));
+    }
+
+    #[actix_rt::test]
+    async fn test_rewrite() {
+        let mut app = init_service(
+            App::new()
+                .wrap(CleanPath::default().use_redirects(None))
+                .service(web::resource("/*").to(|| HttpResponse::Ok())),
+        )
+        .await;
+
+        let cases = vec!["//a//b", "/a//b//", "/a/./b/"];
+        for given in cases.iter() {
+            let req = TestRequest::with_uri(given).to_request();
+            let res = call_service(&mut app, req).await;
+            assert!(res.status().is_success(), "for {}", given);
+        }
+    }
 }